@@ -0,0 +1,142 @@
+use crate::PINECommand;
+
+/// A bitset of `PINECommand`s a negotiated server is known to support.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PINECapabilities(u32);
+
+impl PINECapabilities {
+    pub const READ8: Self = Self(1 << 0);
+    pub const READ16: Self = Self(1 << 1);
+    pub const READ32: Self = Self(1 << 2);
+    pub const READ64: Self = Self(1 << 3);
+    pub const WRITE8: Self = Self(1 << 4);
+    pub const WRITE16: Self = Self(1 << 5);
+    pub const WRITE32: Self = Self(1 << 6);
+    pub const WRITE64: Self = Self(1 << 7);
+    pub const VERSION: Self = Self(1 << 8);
+    pub const SAVE_STATE: Self = Self(1 << 9);
+    pub const LOAD_STATE: Self = Self(1 << 10);
+    pub const TITLE: Self = Self(1 << 11);
+    pub const ID: Self = Self(1 << 12);
+    pub const UUID: Self = Self(1 << 13);
+    pub const GAME_VERSION: Self = Self(1 << 14);
+    pub const STATUS: Self = Self(1 << 15);
+    pub const READ_N: Self = Self(1 << 16);
+    pub const WRITE_N: Self = Self(1 << 17);
+
+    pub const ALL: Self = Self(0x3FFFF);
+
+    /// The 16 original PINE opcodes, excluding `READ_N`/`WRITE_N` — those
+    /// are an extension this crate's own `PINEServer` invented and real
+    /// hosts like PCSX2/RPCS3 don't understand them.
+    pub const STANDARD: Self = Self::ALL.without(Self::READ_N).without(Self::WRITE_N);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    const fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Looks up the declared capability set for a server's self-reported
+    /// `MsgVersion` string. Unrecognized hosts get the full set back, since
+    /// there is no declared profile to narrow it down from.
+    pub(crate) fn for_server_version(version: &str) -> Self {
+        for (needle, capabilities) in SUPPORTED_PROTOCOLS {
+            if version.contains(needle) {
+                return *capabilities;
+            }
+        }
+        Self::ALL
+    }
+}
+
+impl std::ops::BitOr for PINECapabilities {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl PINECommand {
+    /// The capability a client must have negotiated before sending this
+    /// command through `PINE::send_checked`.
+    pub(crate) fn capability(&self) -> PINECapabilities {
+        match self {
+            PINECommand::MsgRead8 { .. } => PINECapabilities::READ8,
+            PINECommand::MsgRead16 { .. } => PINECapabilities::READ16,
+            PINECommand::MsgRead32 { .. } => PINECapabilities::READ32,
+            PINECommand::MsgRead64 { .. } => PINECapabilities::READ64,
+            PINECommand::MsgWrite8 { .. } => PINECapabilities::WRITE8,
+            PINECommand::MsgWrite16 { .. } => PINECapabilities::WRITE16,
+            PINECommand::MsgWrite32 { .. } => PINECapabilities::WRITE32,
+            PINECommand::MsgWrite64 { .. } => PINECapabilities::WRITE64,
+            PINECommand::MsgVersion => PINECapabilities::VERSION,
+            PINECommand::MsgSaveState { .. } => PINECapabilities::SAVE_STATE,
+            PINECommand::MsgLoadState { .. } => PINECapabilities::LOAD_STATE,
+            PINECommand::MsgTitle => PINECapabilities::TITLE,
+            PINECommand::MsgID => PINECapabilities::ID,
+            PINECommand::MsgUUID => PINECapabilities::UUID,
+            PINECommand::MsgGameVersion => PINECapabilities::GAME_VERSION,
+            PINECommand::MsgStatus => PINECapabilities::STATUS,
+            PINECommand::MsgReadN { .. } => PINECapabilities::READ_N,
+            PINECommand::MsgWriteN { .. } => PINECapabilities::WRITE_N,
+            PINECommand::MsgUnimplemented => PINECapabilities::empty(),
+        }
+    }
+}
+
+/// Declared capability sets for known PINE hosts, matched by substring
+/// against the version string each host reports for `MsgVersion`.
+const SUPPORTED_PROTOCOLS: &[(&str, PINECapabilities)] = &[
+    ("PCSX2", PINECapabilities::STANDARD),
+    ("RPCS3", PINECapabilities::STANDARD.without(PINECapabilities::UUID)),
+    (
+        "Duckstation",
+        PINECapabilities::READ8
+            .union(PINECapabilities::READ16)
+            .union(PINECapabilities::READ32)
+            .union(PINECapabilities::READ64)
+            .union(PINECapabilities::WRITE8)
+            .union(PINECapabilities::WRITE16)
+            .union(PINECapabilities::WRITE32)
+            .union(PINECapabilities::WRITE64)
+            .union(PINECapabilities::VERSION)
+            .union(PINECapabilities::TITLE)
+            .union(PINECapabilities::STATUS),
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_excludes_the_bulk_extension_opcodes() {
+        assert!(!PINECapabilities::STANDARD.contains(PINECapabilities::READ_N));
+        assert!(!PINECapabilities::STANDARD.contains(PINECapabilities::WRITE_N));
+        assert!(PINECapabilities::ALL.contains(PINECapabilities::READ_N));
+        assert!(PINECapabilities::ALL.contains(PINECapabilities::WRITE_N));
+    }
+
+    #[test]
+    fn pcsx2_and_rpcs3_do_not_advertise_read_n_or_write_n() {
+        let pcsx2 = PINECapabilities::for_server_version("PCSX2 1.7.0");
+        let rpcs3 = PINECapabilities::for_server_version("RPCS3 v0.0.28");
+
+        assert!(!pcsx2.contains(PINECapabilities::READ_N));
+        assert!(!pcsx2.contains(PINECapabilities::WRITE_N));
+        assert!(!rpcs3.contains(PINECapabilities::READ_N));
+        assert!(!rpcs3.contains(PINECapabilities::WRITE_N));
+    }
+}
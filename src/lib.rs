@@ -2,7 +2,7 @@
 use std::os::unix::net::UnixStream;
 use std::{
     env,
-    io::{Cursor, Read, Write},
+    io::{Cursor, IoSlice, IoSliceMut, Read, Write},
     net::{Ipv4Addr, SocketAddrV4, TcpStream},
     path::{Path, PathBuf},
     str::Utf8Error,
@@ -10,6 +10,15 @@ use std::{
 };
 use thiserror::Error;
 
+mod capabilities;
+mod proto;
+mod registry;
+mod server;
+pub use capabilities::PINECapabilities;
+pub use proto::{ProtoRead, ProtoWrite};
+pub use registry::{PINEProbe, PINERegistry, PINETarget, PINETransport};
+pub use server::{PINEBackend, PINEServer};
+
 #[derive(Error, Debug)]
 pub enum PINEError {
     #[error("IO error: {0}")]
@@ -28,6 +37,18 @@ pub enum PINEError {
     #[cfg(target_family = "unix")]
     #[error("Unix socket not found: {0}")]
     UnixSocket(PathBuf),
+
+    #[error("{command} is not supported by server version {server_version}")]
+    Unsupported {
+        command: PINECommand,
+        server_version: String,
+    },
+
+    #[error("{0} is not a registered PINE target")]
+    UnknownTarget(String),
+
+    #[error("not connected to PINE target {0}")]
+    NotConnected(String),
 }
 
 pub type PINEResult<T> = Result<T, PINEError>;
@@ -35,41 +56,141 @@ pub type PINEResult<T> = Result<T, PINEError>;
 pub struct PINE<T: Read + Write> {
     stream: T,
     mutex: Mutex<()>,
+    version: Option<String>,
+    capabilities: PINECapabilities,
 }
 
 impl<T: Read + Write> PINE<T> {
     pub fn from_stream(stream: T) -> Self {
         let mutex = Mutex::new(());
-        Self { stream, mutex }
+        Self {
+            stream,
+            mutex,
+            version: None,
+            capabilities: PINECapabilities::empty(),
+        }
+    }
+
+    /// Probes the server with `MsgVersion` and caches its version string and
+    /// declared `PINECapabilities`. Called automatically by `connect`,
+    /// `connect_unix` and `connect_tcp`; only needed again if you build a
+    /// `PINE` directly from a stream via `from_stream`.
+    pub fn negotiate(&mut self) -> PINEResult<()> {
+        let mut batch = PINEBatch::new();
+        batch.add(PINECommand::MsgVersion);
+        let version = match self.send(&mut batch)?.into_iter().next() {
+            Some(PINEResponse::ResVersion { version }) => version,
+            _ => String::new(),
+        };
+
+        self.capabilities = PINECapabilities::for_server_version(&version);
+        self.version = Some(version);
+        Ok(())
+    }
+
+    /// The capabilities negotiated with the server, or an empty set if
+    /// `negotiate` has not been called yet.
+    pub fn capabilities(&self) -> PINECapabilities {
+        self.capabilities
+    }
+
+    /// The server's self-reported version string, if negotiated.
+    pub fn server_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn supports(&self, command: &PINECommand) -> bool {
+        self.capabilities.contains(command.capability())
+    }
+
+    /// Like `send`, but first rejects the batch with `PINEError::Unsupported`
+    /// if any of its commands fall outside the negotiated capability set.
+    pub fn send_checked(&mut self, batch: &mut PINEBatch) -> PINEResult<Vec<PINEResponse>> {
+        for command in batch.commands.iter() {
+            if !self.supports(command) {
+                return Err(PINEError::Unsupported {
+                    command: command.clone(),
+                    server_version: self.version.clone().unwrap_or_default(),
+                });
+            }
+        }
+
+        self.send(batch)
     }
 
     pub fn into_inner(self) -> T {
         self.stream
     }
 
-    pub fn send_raw(&mut self, buffer: &[u8]) -> PINEResult<Vec<u8>> {
+    /// Boxes the stream as a trait object, preserving whatever was already
+    /// negotiated. Used by `PINERegistry` to pool connections of different
+    /// transports (Unix and TCP) behind one map.
+    pub(crate) fn into_boxed_dyn(self) -> PINE<Box<dyn registry::ReadWrite>>
+    where
+        T: 'static,
+    {
+        PINE {
+            stream: Box::new(self.stream),
+            mutex: self.mutex,
+            version: self.version,
+            capabilities: self.capabilities,
+        }
+    }
+
+    pub fn send_raw(&mut self, bufs: &mut [IoSlice]) -> PINEResult<Vec<u8>> {
         // Acquire lock
         let _unused = self.mutex.lock().unwrap();
 
-        // Write buffer to socket
-        self.stream.write_all(buffer)?;
+        // Write the command segments straight from the batch, without
+        // first copying them into one contiguous buffer.
+        write_all_vectored(&mut self.stream, bufs)?;
+
+        let res_size = read_response_header(&mut self.stream)?;
+
+        // The header itself accounts for 5 of `res_size`'s bytes, so a
+        // peer claiming less than that is lying about the size of its own
+        // reply. Reject it before the subtraction below underflows, and
+        // read the body incrementally instead of trusting the rest of
+        // `res_size` for an up-front allocation.
+        if res_size < 5 {
+            return Err(PINEError::IO(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "response size smaller than its own header",
+            )));
+        }
+        let res_buffer = self.stream.read_capped((res_size - 5) as u64)?;
+        Ok(res_buffer)
+    }
 
-        // Read response header
-        let res_size = read_u32(&mut self.stream)?;
-        let res_result = read_u8(&mut self.stream)?;
-        if res_result != 0 {
-            return Err(PINEError::CommandFailure);
+    /// Like `send`, but for a single `MsgReadN` command whose bulk payload
+    /// is read straight into `buf` instead of through an intermediate
+    /// `Vec` — the zero-copy path for large memory dumps.
+    pub fn read_n_into(&mut self, mem: u32, buf: &mut [u8]) -> PINEResult<()> {
+        let command = PINECommand::MsgReadN {
+            mem,
+            len: buf.len() as u32,
+        };
+        if !self.supports(&command) {
+            return Err(PINEError::Unsupported {
+                command,
+                server_version: self.version.clone().unwrap_or_default(),
+            });
         }
 
-        // Read buffer
-        let mut res_buffer = vec![0; res_size as usize - 5];
-        self.stream.read_exact(res_buffer.as_mut_slice())?;
-        Ok(res_buffer)
+        let mut batch = PINEBatch::new();
+        batch.add(command);
+
+        let _unused = self.mutex.lock().unwrap();
+        let mut bufs = batch.finalize();
+        write_all_vectored(&mut self.stream, &mut bufs)?;
+        read_response_header(&mut self.stream)?;
+        self.stream.read_exact(buf)?;
+        Ok(())
     }
 
     pub fn send(&mut self, batch: &mut PINEBatch) -> PINEResult<Vec<PINEResponse>> {
-        let buffer = batch.finalize();
-        let res_buffer = self.send_raw(buffer)?;
+        let mut bufs = batch.finalize();
+        let res_buffer = self.send_raw(&mut bufs)?;
 
         // Parse responses
         let mut res = Vec::<PINEResponse>::with_capacity(batch.commands.len());
@@ -77,41 +198,47 @@ impl<T: Read + Write> PINE<T> {
         for command in batch.commands.iter() {
             res.push(match command {
                 PINECommand::MsgRead8 { .. } => PINEResponse::ResRead8 {
-                    val: read_u8(reader)?,
+                    val: reader.read_le_u8()?,
                 },
                 PINECommand::MsgRead16 { .. } => PINEResponse::ResRead16 {
-                    val: read_u16(reader)?,
+                    val: reader.read_le_u16()?,
                 },
                 PINECommand::MsgRead32 { .. } => PINEResponse::ResRead32 {
-                    val: read_u32(reader)?,
+                    val: reader.read_le_u32()?,
                 },
                 PINECommand::MsgRead64 { .. } => PINEResponse::ResRead64 {
-                    val: read_u64(reader)?,
+                    val: reader.read_le_u64()?,
                 },
                 PINECommand::MsgWrite8 { .. } => PINEResponse::ResWrite8,
                 PINECommand::MsgWrite16 { .. } => PINEResponse::ResWrite16,
                 PINECommand::MsgWrite32 { .. } => PINEResponse::ResWrite32,
                 PINECommand::MsgWrite64 { .. } => PINEResponse::ResWrite64,
                 PINECommand::MsgVersion => PINEResponse::ResVersion {
-                    version: read_string(reader)?,
+                    version: reader.read_pine_string()?,
                 },
                 PINECommand::MsgSaveState { .. } => PINEResponse::ResSaveState,
                 PINECommand::MsgLoadState { .. } => PINEResponse::ResLoadState,
                 PINECommand::MsgTitle => PINEResponse::ResTitle {
-                    title: read_string(reader)?,
+                    title: reader.read_pine_string()?,
                 },
                 PINECommand::MsgID => PINEResponse::ResID {
-                    id: read_string(reader)?,
+                    id: reader.read_pine_string()?,
                 },
                 PINECommand::MsgUUID => PINEResponse::ResUUID {
-                    uuid: read_string(reader)?,
+                    uuid: reader.read_pine_string()?,
                 },
                 PINECommand::MsgGameVersion => PINEResponse::ResGameVersion {
-                    version: read_string(reader)?,
+                    version: reader.read_pine_string()?,
                 },
                 PINECommand::MsgStatus => PINEResponse::ResStatus {
-                    status: PINEStatus::from(read_u32(reader)?),
+                    status: PINEStatus::from(reader.read_le_u32()?),
                 },
+                PINECommand::MsgReadN { len, .. } => {
+                    let mut data = vec![0u8; *len as usize];
+                    reader.read_exact(&mut data)?;
+                    PINEResponse::ResReadN { data }
+                }
+                PINECommand::MsgWriteN { .. } => PINEResponse::ResWriteN,
                 PINECommand::MsgUnimplemented => PINEResponse::ResUnimplemented,
             });
         }
@@ -120,27 +247,39 @@ impl<T: Read + Write> PINE<T> {
     }
 }
 
+/// Builds the slot-derived Unix socket path a PINE host listens on:
+/// `$XDG_RUNTIME_DIR/<target>.sock` (Linux), `$TMPDIR/<target>.sock`
+/// (macOS), falling back to `/tmp`, or `<target>.sock.<slot>` when `auto`
+/// is false. Shared by `connect_unix`, `PINEServer::bind_unix` and
+/// `PINERegistry` so the path construction lives in one place.
+#[cfg(target_family = "unix")]
+pub(crate) fn unix_socket_path(target: &str, slot: u16, auto: bool) -> PINEResult<PathBuf> {
+    let env_var = match env::consts::OS {
+        "linux" => "XDG_RUNTIME_DIR",
+        "macos" => "TMPDIR",
+        _ => return Err(PINEError::UnsupportedOS),
+    };
+    let dir = env::var(env_var).unwrap_or(String::from("/tmp"));
+    let filename = if auto {
+        format!("{target}.sock")
+    } else {
+        format!("{target}.sock.{slot}")
+    };
+    Ok(Path::new(&dir).join(filename))
+}
+
 #[cfg(target_family = "unix")]
 impl PINE<UnixStream> {
     pub fn connect_unix(target: &str, slot: u16, auto: bool) -> PINEResult<Self> {
-        let env_var = match env::consts::OS {
-            "linux" => "XDG_RUNTIME_DIR",
-            "macos" => "TMPDIR",
-            _ => return Err(PINEError::UnsupportedOS),
-        };
-        let dir = env::var(env_var).unwrap_or(String::from("/tmp"));
-        let filename = if auto {
-            format!("{target}.sock")
-        } else {
-            format!("{target}.sock.{slot}")
-        };
-        let path = Path::new(&dir).join(filename);
+        let path = unix_socket_path(target, slot, auto)?;
         if !path.exists() {
             return Err(PINEError::UnixSocket(path));
         }
 
         let stream = UnixStream::connect(path)?;
-        Ok(Self::from_stream(stream))
+        let mut pine = Self::from_stream(stream);
+        pine.negotiate()?;
+        Ok(pine)
     }
 
     pub fn connect(target: &str, slot: u16, auto: bool) -> PINEResult<Self> {
@@ -152,8 +291,9 @@ impl PINE<TcpStream> {
     pub fn connect_tcp(addr: Ipv4Addr, slot: u16) -> PINEResult<Self> {
         let socket_addr = SocketAddrV4::new(addr, slot);
         let stream = TcpStream::connect(socket_addr)?;
-        let mutex = Mutex::new(());
-        Ok(Self { stream, mutex })
+        let mut pine = Self::from_stream(stream);
+        pine.negotiate()?;
+        Ok(pine)
     }
 
     #[cfg(target_family = "windows")]
@@ -164,59 +304,84 @@ impl PINE<TcpStream> {
 }
 
 pub struct PINEBatch {
-    buffer: Vec<u8>,
+    // Each command is kept as its own small allocation instead of being
+    // copied into one growing buffer, so `finalize` can hand the socket a
+    // list of `IoSlice`s pointing directly at these segments.
+    segments: Vec<Vec<u8>>,
+    header: [u8; 4],
     commands: Vec<PINECommand>,
 }
 
 impl PINEBatch {
     pub fn new() -> Self {
         Self {
-            buffer: vec![0x00, 0x00, 0x00, 0x00],
+            segments: vec![],
+            header: [0; 4],
             commands: vec![],
-        } // First 4 bytes are for the message length
+        }
     }
 
     pub fn clear(&mut self) {
-        self.buffer.clear();
+        self.segments.clear();
+        self.header = [0; 4];
         self.commands.clear();
     }
 
     pub fn add(&mut self, command: PINECommand) {
-        self.buffer.push(command.to_opcode());
-
-        match command {
-            PINECommand::MsgRead8 { mem } => self.buffer.extend_from_slice(&u32::to_le_bytes(mem)),
-            PINECommand::MsgRead16 { mem } => self.buffer.extend_from_slice(&u32::to_le_bytes(mem)),
-            PINECommand::MsgRead32 { mem } => self.buffer.extend_from_slice(&u32::to_le_bytes(mem)),
-            PINECommand::MsgRead64 { mem } => self.buffer.extend_from_slice(&u32::to_le_bytes(mem)),
+        let mut segment = vec![command.to_opcode()];
+
+        // Matches on a reference so `command` is still whole afterwards,
+        // since `MsgWriteN`'s `data` isn't `Copy`.
+        // Writes into a `Vec<u8>` never fail, so these are infallible.
+        match &command {
+            PINECommand::MsgRead8 { mem } => segment.write_le_u32(*mem).unwrap(),
+            PINECommand::MsgRead16 { mem } => segment.write_le_u32(*mem).unwrap(),
+            PINECommand::MsgRead32 { mem } => segment.write_le_u32(*mem).unwrap(),
+            PINECommand::MsgRead64 { mem } => segment.write_le_u32(*mem).unwrap(),
             PINECommand::MsgWrite8 { mem, val } => {
-                self.buffer.extend_from_slice(&u32::to_le_bytes(mem));
-                self.buffer.push(val);
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u8(*val).unwrap();
             }
             PINECommand::MsgWrite16 { mem, val } => {
-                self.buffer.extend_from_slice(&u32::to_le_bytes(mem));
-                self.buffer.extend_from_slice(&u16::to_le_bytes(val));
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u16(*val).unwrap();
             }
             PINECommand::MsgWrite32 { mem, val } => {
-                self.buffer.extend_from_slice(&u32::to_le_bytes(mem));
-                self.buffer.extend_from_slice(&u32::to_le_bytes(val));
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u32(*val).unwrap();
             }
             PINECommand::MsgWrite64 { mem, val } => {
-                self.buffer.extend_from_slice(&u32::to_le_bytes(mem));
-                self.buffer.extend_from_slice(&u64::to_le_bytes(val));
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u64(*val).unwrap();
+            }
+            PINECommand::MsgSaveState { sta } => segment.write_le_u8(*sta).unwrap(),
+            PINECommand::MsgLoadState { sta } => segment.write_le_u8(*sta).unwrap(),
+            PINECommand::MsgReadN { mem, len } => {
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u32(*len).unwrap();
+            }
+            PINECommand::MsgWriteN { mem, data } => {
+                segment.write_le_u32(*mem).unwrap();
+                segment.write_le_u32(data.len() as u32).unwrap();
+                segment.extend_from_slice(data);
             }
-            PINECommand::MsgSaveState { sta } => self.buffer.push(sta),
-            PINECommand::MsgLoadState { sta } => self.buffer.push(sta),
             _ => {}
         }
 
+        self.segments.push(segment);
         self.commands.push(command);
     }
 
-    fn finalize(&mut self) -> &[u8] {
-        let size = self.buffer.len() as u32;
-        self.buffer.splice(0..4, u32::to_le_bytes(size));
-        self.buffer.as_slice()
+    /// Builds the length-prefixed header and returns `IoSlice`s covering the
+    /// header and every command segment, ready for a single vectored write.
+    fn finalize(&mut self) -> Vec<IoSlice<'_>> {
+        let size: usize = 4 + self.segments.iter().map(Vec::len).sum::<usize>();
+        self.header = (size as u32).to_le_bytes();
+
+        let mut bufs = Vec::with_capacity(self.segments.len() + 1);
+        bufs.push(IoSlice::new(&self.header));
+        bufs.extend(self.segments.iter().map(|segment| IoSlice::new(segment)));
+        bufs
     }
 }
 
@@ -237,7 +402,7 @@ impl Default for PINEBatch {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum PINECommand {
     MsgRead8 { mem: u32 } = 0,
     MsgRead16 { mem: u32 } = 1,
@@ -255,6 +420,10 @@ pub enum PINECommand {
     MsgUUID = 13,
     MsgGameVersion = 14,
     MsgStatus = 15,
+    /// Reads `len` bytes starting at `mem` in one round-trip.
+    MsgReadN { mem: u32, len: u32 } = 16,
+    /// Writes `data` starting at `mem` in one round-trip.
+    MsgWriteN { mem: u32, data: Vec<u8> } = 17,
     MsgUnimplemented = 255,
 }
 
@@ -277,6 +446,8 @@ impl PINECommand {
             PINECommand::MsgUUID => 13,
             PINECommand::MsgGameVersion => 14,
             PINECommand::MsgStatus => 15,
+            PINECommand::MsgReadN { .. } => 16,
+            PINECommand::MsgWriteN { .. } => 17,
             PINECommand::MsgUnimplemented => 255,
         }
     }
@@ -313,6 +484,8 @@ pub enum PINEResponse {
     ResUUID { uuid: String },
     ResGameVersion { version: String },
     ResStatus { status: PINEStatus },
+    ResReadN { data: Vec<u8> },
+    ResWriteN,
     ResUnimplemented,
 }
 
@@ -348,31 +521,131 @@ impl std::fmt::Display for PINEStatus {
     }
 }
 
-macro_rules! read_impl {
-    ($reader:ident, $ty:ident, $size:expr) => {
-        let mut buf: [u8; $size] = [0; $size];
-        $reader.read_exact(&mut buf)?;
-        return Ok($ty::from_le_bytes(buf));
-    };
-}
+/// Reads the response header (size + result byte) into local buffers via
+/// vectored reads, and returns the declared response size, which counts the
+/// 5 header bytes as well as the body.
+fn read_response_header<R: Read>(reader: &mut R) -> PINEResult<u32> {
+    let mut size_buf = [0u8; 4];
+    let mut result_buf = [0u8; 1];
+    read_exact_vectored(
+        reader,
+        &mut [
+            IoSliceMut::new(&mut size_buf),
+            IoSliceMut::new(&mut result_buf),
+        ],
+    )?;
+    if result_buf[0] != 0 {
+        return Err(PINEError::CommandFailure);
+    }
 
-fn read_u64<R: Read>(reader: &mut R) -> Result<u64, std::io::Error> {
-    read_impl!(reader, u64, 8);
-}
-fn read_u32<R: Read>(reader: &mut R) -> Result<u32, std::io::Error> {
-    read_impl!(reader, u32, 4);
+    Ok(u32::from_le_bytes(size_buf))
 }
-fn read_u16<R: Read>(reader: &mut R) -> Result<u16, std::io::Error> {
-    read_impl!(reader, u16, 2);
+
+/// Writes every buffer in `bufs` to `writer`, advancing past whatever a
+/// short write already consumed. `Write::write_all_vectored` is not yet
+/// stable, so this loops over `write_vectored` by hand.
+fn write_all_vectored<W: Write>(writer: &mut W, bufs: &mut [IoSlice]) -> std::io::Result<()> {
+    let mut bufs = bufs;
+    while !bufs.is_empty() {
+        match writer.write_vectored(bufs) {
+            Ok(0) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
+            Ok(n) => IoSlice::advance_slices(&mut bufs, n),
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
 }
-fn read_u8<R: Read>(reader: &mut R) -> Result<u8, std::io::Error> {
-    read_impl!(reader, u8, 1);
+
+/// Fills every buffer in `bufs` from `reader`, the vectored counterpart of
+/// `Read::read_exact`.
+fn read_exact_vectored<R: Read>(reader: &mut R, bufs: &mut [IoSliceMut]) -> std::io::Result<()> {
+    let mut bufs = bufs;
+    while !bufs.is_empty() {
+        match reader.read_vectored(bufs) {
+            Ok(0) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+            Ok(n) => IoSliceMut::advance_slices(&mut bufs, n),
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
 }
-fn read_string<R: Read>(reader: &mut R) -> Result<String, std::io::Error> {
-    let size = read_u32(reader)?;
-    let mut buffer: Vec<u8> = vec![0; size as usize];
-    reader.read_exact(buffer.as_mut_slice())?;
-    let mut s = std::str::from_utf8(&buffer).unwrap().to_string();
-    s.pop(); // Remove null terminator
-    Ok(s)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A duplex in-memory stream standing in for the socket: reads come
+    /// from a canned server reply, writes are discarded.
+    struct MockStream {
+        input: Cursor<Vec<u8>>,
+    }
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Builds a canned `MsgVersion` reply: a response header followed by a
+    /// PINE string for `version`.
+    fn version_reply(version: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.write_pine_string(version).unwrap();
+
+        let mut reply = (5 + body.len() as u32).to_le_bytes().to_vec();
+        reply.push(0); // success
+        reply.extend_from_slice(&body);
+        reply
+    }
+
+    #[test]
+    fn send_raw_rejects_a_response_size_smaller_than_its_own_header() {
+        let mut reply = 0u32.to_le_bytes().to_vec(); // declares less than the header itself
+        reply.push(0); // success
+
+        let mut pine = PINE::from_stream(MockStream {
+            input: Cursor::new(reply),
+        });
+        let mut batch = PINEBatch::new();
+        batch.add(PINECommand::MsgVersion);
+
+        let err = pine.send(&mut batch).unwrap_err();
+        assert!(matches!(err, PINEError::IO(_)));
+    }
+
+    #[test]
+    fn read_n_into_rejects_when_the_server_has_not_negotiated_read_n() {
+        let mut pine = PINE::from_stream(MockStream {
+            input: Cursor::new(version_reply("PCSX2 1.7.0")),
+        });
+        pine.negotiate().unwrap();
+        assert!(!pine.supports(&PINECommand::MsgReadN { mem: 0, len: 0 }));
+
+        let mut buf = [0u8; 4];
+        let err = pine.read_n_into(0, &mut buf).unwrap_err();
+        assert!(matches!(err, PINEError::Unsupported { .. }));
+    }
 }
+
@@ -0,0 +1,93 @@
+use std::io::{Read, Write};
+
+use crate::PINEError;
+
+/// Little-endian decoding primitives shared by `PINE::send` and
+/// `PINEServer`; `ProtoWrite` is the encoding side, used by `PINEBatch::add`
+/// and `PINEServer`, so the wire format lives in exactly one place.
+pub trait ProtoRead: Read {
+    fn read_le_u8(&mut self) -> std::io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf)?;
+        Ok(u8::from_le_bytes(buf))
+    }
+
+    fn read_le_u16(&mut self) -> std::io::Result<u16> {
+        let mut buf = [0u8; 2];
+        self.read_exact(&mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn read_le_u32(&mut self) -> std::io::Result<u32> {
+        let mut buf = [0u8; 4];
+        self.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_le_u64(&mut self) -> std::io::Result<u64> {
+        let mut buf = [0u8; 8];
+        self.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Reads exactly `size` bytes, but never preallocates more than the
+    /// sender has actually delivered — the declared size is only trusted
+    /// as far as bytes keep arriving, so a frame that lies about its
+    /// length runs out of data instead of triggering a huge allocation.
+    fn read_capped(&mut self, size: u64) -> std::io::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.take(size).read_to_end(&mut buffer)?;
+        if (buffer.len() as u64) < size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "declared length ran past the available data",
+            ));
+        }
+        Ok(buffer)
+    }
+
+    /// Reads a PINE string: a `u32` length (the payload, including the
+    /// trailing NUL) followed by that many bytes.
+    ///
+    /// Never panics on malformed UTF-8; see `read_capped` for how the
+    /// declared length is kept from triggering a huge allocation.
+    fn read_pine_string(&mut self) -> Result<String, PINEError> {
+        let size = self.read_le_u32()? as u64;
+        let buffer = self.read_capped(size)?;
+
+        let mut s = std::str::from_utf8(&buffer)?.to_string();
+        s.pop(); // Remove null terminator
+        Ok(s)
+    }
+}
+
+impl<R: Read + ?Sized> ProtoRead for R {}
+
+pub trait ProtoWrite: Write {
+    fn write_le_u8(&mut self, val: u8) -> std::io::Result<()> {
+        self.write_all(&val.to_le_bytes())
+    }
+
+    fn write_le_u16(&mut self, val: u16) -> std::io::Result<()> {
+        self.write_all(&val.to_le_bytes())
+    }
+
+    fn write_le_u32(&mut self, val: u32) -> std::io::Result<()> {
+        self.write_all(&val.to_le_bytes())
+    }
+
+    fn write_le_u64(&mut self, val: u64) -> std::io::Result<()> {
+        self.write_all(&val.to_le_bytes())
+    }
+
+    /// Writes a PINE string: a `u32` length (including the trailing NUL)
+    /// followed by the bytes and the NUL itself.
+    fn write_pine_string(&mut self, val: &str) -> std::io::Result<()> {
+        let bytes = val.as_bytes();
+        self.write_le_u32((bytes.len() + 1) as u32)?;
+        self.write_all(bytes)?;
+        self.write_le_u8(0)
+    }
+}
+
+impl<W: Write + ?Sized> ProtoWrite for W {}
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    net::{Ipv4Addr, SocketAddrV4, TcpStream},
+    time::Duration,
+};
+
+use crate::{PINEBatch, PINECommand, PINEError, PINEResponse, PINEResult, PINEStatus, PINE};
+
+pub(crate) trait ReadWrite: Read + Write {}
+impl<T: Read + Write> ReadWrite for T {}
+
+/// How a `PINETarget` is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PINETransport {
+    #[cfg(target_family = "unix")]
+    Unix,
+    Tcp,
+}
+
+/// A routing entry: a logical emulator name, its default PINE slot, and
+/// which transport it's reached over.
+#[derive(Clone, Debug)]
+pub struct PINETarget {
+    pub name: String,
+    pub default_slot: u16,
+    pub transport: PINETransport,
+}
+
+const KNOWN_TARGETS: &[(&str, u16, PINETransport)] = &[
+    #[cfg(target_family = "unix")]
+    ("pcsx2", 28011, PINETransport::Unix),
+    #[cfg(target_family = "unix")]
+    ("rpcs3", 28012, PINETransport::Unix),
+    #[cfg(target_family = "unix")]
+    ("duckstation", 28011, PINETransport::Unix),
+];
+
+/// The result of probing one `PINETarget` with `PINERegistry::probe_all`.
+#[derive(Clone, Debug)]
+pub struct PINEProbe {
+    pub target: String,
+    pub reachable: bool,
+    pub status: Option<PINEStatus>,
+}
+
+/// A table of known PINE targets (`"pcsx2"`, `"rpcs3"`, `"duckstation"`,
+/// ...) plus a pool of live connections, so a program can talk to several
+/// emulators at once without re-deriving each one's slot/socket-path by
+/// hand.
+pub struct PINERegistry {
+    targets: HashMap<String, PINETarget>,
+    connections: HashMap<String, PINE<Box<dyn ReadWrite>>>,
+}
+
+impl PINERegistry {
+    pub fn new() -> Self {
+        let mut targets = HashMap::new();
+        for (name, default_slot, transport) in KNOWN_TARGETS {
+            targets.insert(
+                name.to_string(),
+                PINETarget {
+                    name: name.to_string(),
+                    default_slot: *default_slot,
+                    transport: *transport,
+                },
+            );
+        }
+
+        Self {
+            targets,
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Adds or overrides the routing entry for `name`.
+    pub fn register(&mut self, name: &str, default_slot: u16, transport: PINETransport) {
+        self.targets.insert(
+            name.to_string(),
+            PINETarget {
+                name: name.to_string(),
+                default_slot,
+                transport,
+            },
+        );
+    }
+
+    /// Opens a connection to `name` and adds it to the pool.
+    pub fn connect(&mut self, name: &str) -> PINEResult<()> {
+        let target = self
+            .targets
+            .get(name)
+            .ok_or_else(|| PINEError::UnknownTarget(name.to_string()))?;
+        let pine = open_target(target)?;
+        self.connections.insert(name.to_string(), pine);
+        Ok(())
+    }
+
+    /// Dispatches `batch` to the pooled connection for `name`.
+    pub fn send(&mut self, name: &str, batch: &mut PINEBatch) -> PINEResult<Vec<PINEResponse>> {
+        let pine = self
+            .connections
+            .get_mut(name)
+            .ok_or_else(|| PINEError::NotConnected(name.to_string()))?;
+        pine.send(batch)
+    }
+
+    /// Walks the routing table and reports which targets are currently
+    /// reachable (their socket exists, or a TCP connect succeeds), along
+    /// with their negotiated status where a connection could be opened.
+    pub fn probe_all(&self) -> Vec<PINEProbe> {
+        let mut targets: Vec<&PINETarget> = self.targets.values().collect();
+        targets.sort_by(|a, b| a.name.cmp(&b.name));
+
+        targets
+            .into_iter()
+            .map(|target| {
+                let reachable = is_reachable(target);
+                let status = reachable
+                    .then(|| open_target(target).ok())
+                    .flatten()
+                    .and_then(|mut pine| {
+                        let mut batch = PINEBatch::new();
+                        batch.add(PINECommand::MsgStatus);
+                        match pine.send(&mut batch).ok()?.into_iter().next() {
+                            Some(PINEResponse::ResStatus { status }) => Some(status),
+                            _ => None,
+                        }
+                    });
+
+                PINEProbe {
+                    target: target.name.clone(),
+                    reachable,
+                    status,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for PINERegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_reachable(target: &PINETarget) -> bool {
+    match target.transport {
+        #[cfg(target_family = "unix")]
+        PINETransport::Unix => crate::unix_socket_path(&target.name, target.default_slot, true)
+            .map(|path| path.exists())
+            .unwrap_or(false),
+        PINETransport::Tcp => {
+            let addr = SocketAddrV4::new(Ipv4Addr::LOCALHOST, target.default_slot);
+            TcpStream::connect_timeout(&addr.into(), Duration::from_millis(200)).is_ok()
+        }
+    }
+}
+
+fn open_target(target: &PINETarget) -> PINEResult<PINE<Box<dyn ReadWrite>>> {
+    match target.transport {
+        #[cfg(target_family = "unix")]
+        PINETransport::Unix => {
+            let stream = PINE::connect_unix(&target.name, target.default_slot, true)?;
+            Ok(stream.into_boxed_dyn())
+        }
+        PINETransport::Tcp => {
+            let stream = PINE::connect_tcp(Ipv4Addr::LOCALHOST, target.default_slot)?;
+            Ok(stream.into_boxed_dyn())
+        }
+    }
+}
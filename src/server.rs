@@ -0,0 +1,456 @@
+#[cfg(target_family = "unix")]
+use std::os::unix::net::UnixListener;
+use std::{
+    io::{Cursor, Read, Write},
+    net::{Ipv4Addr, SocketAddrV4, TcpListener},
+    sync::Arc,
+    thread,
+};
+
+use crate::{PINEResult, PINEStatus, ProtoRead, ProtoWrite};
+
+/// Implemented by a program that wants to act as a PINE target.
+///
+/// Every method mirrors a `PINECommand` variant: the server decodes the
+/// opcodes sent by a client and dispatches them here one at a time.
+/// Reads are infallible (an out-of-range `addr` is the backend's concern,
+/// e.g. returning `0`); the state-altering commands return `bool` so the
+/// server can report a non-zero result byte back to the client on failure.
+pub trait PINEBackend {
+    fn read8(&self, addr: u32) -> u8;
+    fn read16(&self, addr: u32) -> u16;
+    fn read32(&self, addr: u32) -> u32;
+    fn read64(&self, addr: u32) -> u64;
+
+    fn write8(&self, addr: u32, val: u8) -> bool;
+    fn write16(&self, addr: u32, val: u16) -> bool;
+    fn write32(&self, addr: u32, val: u32) -> bool;
+    fn write64(&self, addr: u32, val: u64) -> bool;
+
+    fn title(&self) -> String;
+    fn id(&self) -> String;
+    fn uuid(&self) -> String;
+    fn game_version(&self) -> String;
+    fn version(&self) -> String;
+    fn status(&self) -> PINEStatus;
+
+    fn save_state(&self, slot: u8) -> bool;
+    fn load_state(&self, slot: u8) -> bool;
+}
+
+enum PINEListener {
+    #[cfg(target_family = "unix")]
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+/// A PINE server: accepts connections on a slot-derived socket and answers
+/// `PINEBatch` requests against a `PINEBackend`.
+///
+/// Each accepted connection is handled on its own thread, so `B` must be
+/// `Send + Sync`.
+pub struct PINEServer<B: PINEBackend + Send + Sync + 'static> {
+    listener: PINEListener,
+    backend: Arc<B>,
+}
+
+#[cfg(target_family = "unix")]
+impl<B: PINEBackend + Send + Sync + 'static> PINEServer<B> {
+    pub fn bind_unix(target: &str, slot: u16, auto: bool, backend: B) -> PINEResult<Self> {
+        let path = crate::unix_socket_path(target, slot, auto)?;
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        Ok(Self {
+            listener: PINEListener::Unix(listener),
+            backend: Arc::new(backend),
+        })
+    }
+
+    pub fn bind(target: &str, slot: u16, auto: bool, backend: B) -> PINEResult<Self> {
+        Self::bind_unix(target, slot, auto, backend)
+    }
+}
+
+impl<B: PINEBackend + Send + Sync + 'static> PINEServer<B> {
+    pub fn bind_tcp(addr: Ipv4Addr, slot: u16, backend: B) -> PINEResult<Self> {
+        let socket_addr = SocketAddrV4::new(addr, slot);
+        let listener = TcpListener::bind(socket_addr)?;
+        Ok(Self {
+            listener: PINEListener::Tcp(listener),
+            backend: Arc::new(backend),
+        })
+    }
+
+    #[cfg(target_family = "windows")]
+    pub fn bind(_target: &str, slot: u16, _auto: bool, backend: B) -> PINEResult<Self> {
+        let addr = Ipv4Addr::new(127, 0, 0, 1);
+        Self::bind_tcp(addr, slot, backend)
+    }
+
+    /// Accepts connections forever, handling each one on its own thread.
+    ///
+    /// A failed accept (e.g. the process is out of file descriptors) is
+    /// logged and skipped rather than tearing down the whole listener —
+    /// one bad connection attempt shouldn't take down every other client
+    /// already being served.
+    pub fn listen(&self) -> PINEResult<()> {
+        match &self.listener {
+            #[cfg(target_family = "unix")]
+            PINEListener::Unix(listener) => {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            eprintln!("PINEServer: accept failed: {err}");
+                            continue;
+                        }
+                    };
+                    let backend = Arc::clone(&self.backend);
+                    thread::spawn(move || {
+                        let _ = handle_stream(stream, backend.as_ref());
+                    });
+                }
+            }
+            PINEListener::Tcp(listener) => {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            eprintln!("PINEServer: accept failed: {err}");
+                            continue;
+                        }
+                    };
+                    let backend = Arc::clone(&self.backend);
+                    thread::spawn(move || {
+                        let _ = handle_stream(stream, backend.as_ref());
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Upper bound on anything a client declares the length of: a batch frame,
+/// or a `MsgReadN`/`MsgWriteN` range.
+///
+/// These lengths are read before the bytes (or, for `MsgReadN`, any bytes
+/// at all) they describe have arrived, so none of them can be trusted for
+/// an up-front allocation or loop bound: a client can claim
+/// `len = 0xFFFFFFFF` and send nothing else. Cap them to something no real
+/// request would ever need, and read declared-length data incrementally
+/// via `read_capped` rather than preallocating it.
+const MAX_DECLARED_LEN: u32 = 16 * 1024 * 1024;
+
+fn handle_stream<S: Read + Write, B: PINEBackend>(mut stream: S, backend: &B) -> PINEResult<()> {
+    loop {
+        let len = match stream.read_le_u32() {
+            Ok(len) => len,
+            Err(_) => return Ok(()), // client disconnected
+        };
+        if (len as usize) < 4 || len > MAX_DECLARED_LEN {
+            return Ok(());
+        }
+
+        let payload = stream.read_capped(len as u64 - 4)?;
+        let mut reader = Cursor::new(payload);
+        let body_len = reader.get_ref().len() as u64;
+        let mut response = Vec::new();
+        let mut failed = false;
+
+        while reader.position() < body_len {
+            let opcode = reader.read_le_u8()?;
+            match opcode {
+                0 => {
+                    let mem = reader.read_le_u32()?;
+                    response.write_le_u8(backend.read8(mem)).unwrap();
+                }
+                1 => {
+                    let mem = reader.read_le_u32()?;
+                    response.write_le_u16(backend.read16(mem)).unwrap();
+                }
+                2 => {
+                    let mem = reader.read_le_u32()?;
+                    response.write_le_u32(backend.read32(mem)).unwrap();
+                }
+                3 => {
+                    let mem = reader.read_le_u32()?;
+                    response.write_le_u64(backend.read64(mem)).unwrap();
+                }
+                4 => {
+                    let mem = reader.read_le_u32()?;
+                    let val = reader.read_le_u8()?;
+                    failed |= !backend.write8(mem, val);
+                }
+                5 => {
+                    let mem = reader.read_le_u32()?;
+                    let val = reader.read_le_u16()?;
+                    failed |= !backend.write16(mem, val);
+                }
+                6 => {
+                    let mem = reader.read_le_u32()?;
+                    let val = reader.read_le_u32()?;
+                    failed |= !backend.write32(mem, val);
+                }
+                7 => {
+                    let mem = reader.read_le_u32()?;
+                    let val = reader.read_le_u64()?;
+                    failed |= !backend.write64(mem, val);
+                }
+                8 => response.write_pine_string(&backend.version()).unwrap(),
+                9 => {
+                    let sta = reader.read_le_u8()?;
+                    failed |= !backend.save_state(sta);
+                }
+                10 => {
+                    let sta = reader.read_le_u8()?;
+                    failed |= !backend.load_state(sta);
+                }
+                11 => response.write_pine_string(&backend.title()).unwrap(),
+                12 => response.write_pine_string(&backend.id()).unwrap(),
+                13 => response.write_pine_string(&backend.uuid()).unwrap(),
+                14 => response.write_pine_string(&backend.game_version()).unwrap(),
+                15 => {
+                    let status = match backend.status() {
+                        PINEStatus::Running => 0u32,
+                        PINEStatus::Paused => 1,
+                        PINEStatus::Shutdown => 2,
+                        PINEStatus::Unknown => 3,
+                    };
+                    response.write_le_u32(status).unwrap();
+                }
+                16 => {
+                    let mem = reader.read_le_u32()?;
+                    let len = reader.read_le_u32()?;
+                    // `len` drives the loop bound below with no bytes of its
+                    // own to run out of, unlike `MsgWriteN`'s payload — so it
+                    // needs an explicit cap rather than `read_capped`'s. The
+                    // cap is against the *whole batch's* response so far
+                    // (not just this command's `len`), since a batch can
+                    // pack many small MsgReadN commands that each request a
+                    // large range and amplify into an unbounded response.
+                    if response.len() as u64 + len as u64 > MAX_DECLARED_LEN as u64 {
+                        failed = true;
+                        break;
+                    }
+                    for offset in 0..len {
+                        response
+                            .write_le_u8(backend.read8(mem.wrapping_add(offset)))
+                            .unwrap();
+                    }
+                }
+                17 => {
+                    let mem = reader.read_le_u32()?;
+                    let len = reader.read_le_u32()?;
+                    let data = reader.read_capped(len as u64)?;
+                    for (offset, byte) in data.into_iter().enumerate() {
+                        failed |= !backend.write8(mem.wrapping_add(offset as u32), byte);
+                    }
+                }
+                255 => {}
+                _ => {
+                    failed = true;
+                    break;
+                }
+            }
+
+            if failed {
+                break;
+            }
+        }
+
+        if failed {
+            stream.write_le_u32(5)?;
+            stream.write_le_u8(1)?;
+        } else {
+            stream.write_le_u32((response.len() + 5) as u32)?;
+            stream.write_le_u8(0)?;
+            stream.write_all(&response)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc, sync::Mutex};
+
+    /// A duplex in-memory stream: reads come from a fixed buffer, writes go
+    /// to a shared one so the test can inspect them after `handle_stream`
+    /// (which takes its stream by value) has consumed it.
+    struct MockStream {
+        input: Cursor<Vec<u8>>,
+        output: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Read for MockStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MockStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.output.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct TestBackend {
+        writes: Mutex<Vec<(u32, u8)>>,
+    }
+
+    impl TestBackend {
+        fn new() -> Self {
+            Self {
+                writes: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl PINEBackend for TestBackend {
+        fn read8(&self, addr: u32) -> u8 {
+            addr as u8
+        }
+        fn read16(&self, _addr: u32) -> u16 {
+            0
+        }
+        fn read32(&self, _addr: u32) -> u32 {
+            0
+        }
+        fn read64(&self, _addr: u32) -> u64 {
+            0
+        }
+
+        fn write8(&self, addr: u32, val: u8) -> bool {
+            self.writes.lock().unwrap().push((addr, val));
+            true
+        }
+        fn write16(&self, _addr: u32, _val: u16) -> bool {
+            true
+        }
+        fn write32(&self, _addr: u32, _val: u32) -> bool {
+            true
+        }
+        fn write64(&self, _addr: u32, _val: u64) -> bool {
+            true
+        }
+
+        fn title(&self) -> String {
+            String::new()
+        }
+        fn id(&self) -> String {
+            String::new()
+        }
+        fn uuid(&self) -> String {
+            String::new()
+        }
+        fn game_version(&self) -> String {
+            String::new()
+        }
+        fn version(&self) -> String {
+            String::new()
+        }
+        fn status(&self) -> PINEStatus {
+            PINEStatus::Running
+        }
+
+        fn save_state(&self, _slot: u8) -> bool {
+            true
+        }
+        fn load_state(&self, _slot: u8) -> bool {
+            true
+        }
+    }
+
+    fn run(frame: Vec<u8>, backend: &TestBackend) -> Vec<u8> {
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let stream = MockStream {
+            input: Cursor::new(frame),
+            output: Rc::clone(&output),
+        };
+        handle_stream(stream, backend).unwrap();
+        Rc::try_unwrap(output).unwrap().into_inner()
+    }
+
+    fn framed_batch(body: Vec<u8>) -> Vec<u8> {
+        let mut frame = (4 + body.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(&body);
+        frame
+    }
+
+    #[test]
+    fn round_trips_read8() {
+        let mut body = vec![0u8]; // opcode 0: MsgRead8
+        body.extend_from_slice(&42u32.to_le_bytes());
+
+        let output = run(framed_batch(body), &TestBackend::new());
+
+        let mut expected = 6u32.to_le_bytes().to_vec(); // 5-byte header + 1 data byte
+        expected.push(0); // success
+        expected.push(42); // read8(42) == 42 per TestBackend
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn rejects_batch_larger_than_max_declared_len() {
+        // Only the 4-byte length prefix is needed: a batch this large is
+        // rejected before a single body byte is read.
+        let frame = (MAX_DECLARED_LEN + 1).to_le_bytes().to_vec();
+
+        let output = run(frame, &TestBackend::new());
+
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn rejects_msg_read_n_whose_aggregate_response_exceeds_the_cap() {
+        let mut body = Vec::new();
+        // A small MsgReadN that succeeds and grows the response a little...
+        body.push(16); // opcode 16: MsgReadN
+        body.extend_from_slice(&0u32.to_le_bytes()); // mem
+        body.extend_from_slice(&10u32.to_le_bytes()); // len
+        // ...followed by one whose own len is within bounds but, combined
+        // with the first response's bytes, pushes the batch's total
+        // response past MAX_DECLARED_LEN.
+        body.push(16);
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&MAX_DECLARED_LEN.to_le_bytes());
+
+        let output = run(framed_batch(body), &TestBackend::new());
+
+        // The failure response: a 5-byte header and a non-zero result byte,
+        // with no further command processed.
+        let mut expected = 5u32.to_le_bytes().to_vec();
+        expected.push(1);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn msg_write_n_wraps_address_math_instead_of_overflowing() {
+        let data: Vec<u8> = (0..32).collect();
+        let mut body = vec![17]; // opcode 17: MsgWriteN
+        body.extend_from_slice(&0xFFFFFFF0u32.to_le_bytes()); // mem, 16 bytes below the wrap back to 0
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&data);
+
+        let backend = TestBackend::new();
+        let output = run(framed_batch(body), &backend);
+
+        let mut expected = 5u32.to_le_bytes().to_vec();
+        expected.push(0); // success
+        assert_eq!(output, expected);
+
+        // offset 16 wraps 0xFFFFFFF0 + 16 back to address 0 instead of
+        // panicking (debug) or silently writing past it (release).
+        assert!(backend.writes.lock().unwrap().contains(&(0, data[16])));
+    }
+}